@@ -0,0 +1,152 @@
+//! On-disk cache for Steam web-API auth tickets.
+//!
+//! Tickets are short-lived, so a cache hit is only honoured when the entry is
+//! younger than the caller-supplied TTL. Any I/O or parse error is treated as
+//! a cache miss rather than a hard failure -- losing the cache should never
+//! stop the tool from acquiring a fresh ticket.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Directory the cache lives in: `~/.cache/steam-auth/tickets`.
+fn cache_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".cache").join("steam-auth").join("tickets")
+}
+
+/// Hashes an identity string into a filesystem-safe cache file name.
+///
+/// Sanitizing by substituting characters would collapse distinct identities
+/// that differ only in punctuation (`"BitCraft Server"` vs `"BitCraft.Server"`)
+/// onto the same file, so we hash the raw identity instead.
+fn cache_file_name(identity: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    identity.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+fn cache_path(identity: &str) -> PathBuf {
+    cache_dir().join(cache_file_name(identity))
+}
+
+/// Creates the cache directory and restricts it to the current user on unix.
+#[cfg(unix)]
+fn ensure_cache_dir() -> io::Result<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = cache_dir();
+    fs::create_dir_all(&dir)?;
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
+    Ok(dir)
+}
+
+#[cfg(not(unix))]
+fn ensure_cache_dir() -> io::Result<PathBuf> {
+    let dir = cache_dir();
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Reads a cached ticket for `identity`, returning `None` on a miss, a
+/// permission/parse error, or an entry older than `ttl`.
+pub fn read_cached_ticket(identity: &str, ttl: Duration) -> Option<Vec<u8>> {
+    let contents = fs::read_to_string(cache_path(identity)).ok()?;
+    let mut lines = contents.lines();
+    let acquired_at: u64 = lines.next()?.trim().parse().ok()?;
+    let hex_ticket = lines.next()?.trim();
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    if now.saturating_sub(acquired_at) > ttl.as_secs() {
+        return None;
+    }
+
+    hex_decode(hex_ticket)
+}
+
+/// Writes `ticket` to the cache for `identity`, stamped with the current time.
+///
+/// The file is created with 0600 permissions on unix.
+pub fn write_cached_ticket(identity: &str, ticket: &[u8]) -> io::Result<()> {
+    ensure_cache_dir()?;
+
+    let path = cache_path(identity);
+    let acquired_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let hex_ticket = hex_encode(ticket);
+
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&path)?;
+        writeln!(file, "{}\n{}", acquired_at, hex_ticket)?;
+    }
+
+    #[cfg(not(unix))]
+    {
+        use std::io::Write;
+        let mut file = fs::File::create(&path)?;
+        writeln!(file, "{}\n{}", acquired_at, hex_ticket)?;
+    }
+
+    Ok(())
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return None;
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_round_trip() {
+        let bytes = vec![0x00, 0x1a, 0xff, 0x42];
+        assert_eq!(hex_decode(&hex_encode(&bytes)).unwrap(), bytes);
+    }
+
+    #[test]
+    fn hex_decode_rejects_odd_length() {
+        assert_eq!(hex_decode("abc"), None);
+    }
+
+    #[test]
+    fn hex_decode_rejects_non_ascii() {
+        assert_eq!(hex_decode("中中"), None);
+    }
+
+    #[test]
+    fn hex_decode_rejects_non_hex_ascii() {
+        assert_eq!(hex_decode("zz"), None);
+    }
+
+    #[test]
+    fn cache_file_name_disambiguates_lookalike_identities() {
+        assert_ne!(cache_file_name("BitCraft Server"), cache_file_name("BitCraft.Server"));
+    }
+
+    #[test]
+    fn cache_file_name_is_stable() {
+        assert_eq!(cache_file_name("BitCraftApiServer"), cache_file_name("BitCraftApiServer"));
+    }
+}
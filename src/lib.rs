@@ -0,0 +1,207 @@
+//! Library surface for acquiring Steam web-API authentication tickets.
+//!
+//! `main.rs` is a thin CLI wrapper over [`SteamAuth`].
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use steamworks::{CallbackHandle, Client, TicketForWebApiResponse};
+use tokio::sync::oneshot;
+use tracing::{debug, error, info, instrument, warn};
+
+pub mod cache;
+pub mod verify;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Default timeout used by [`SteamAuth::request_ticket`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+/// Default retry count used by [`SteamAuth::request_ticket`].
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// Slot the `TicketForWebApiResponse` callback delivers into. A fresh sender
+/// is installed before each request and the callback takes it the moment a
+/// response arrives (filling it on success, dropping it on failure), so
+/// waiting is a single `await` instead of a poll loop.
+type PendingTicket = Arc<Mutex<Option<oneshot::Sender<Vec<u8>>>>>;
+
+/// A connected Steam client ready to mint web-API auth tickets for a given identity.
+pub struct SteamAuth {
+    client: Client,
+    identity: String,
+    pending: PendingTicket,
+    _callback: CallbackHandle,
+    dispatcher_task: tokio::task::AbortHandle,
+}
+
+impl SteamAuth {
+    /// Initializes the Steam client, registers the ticket callback, confirms
+    /// the local Steam user is logged in, and starts the background task
+    /// that pumps `run_callbacks`.
+    #[instrument(fields(identity = %app_identity))]
+    pub fn connect(app_identity: &str) -> Result<Self> {
+        let client = Client::init()?;
+
+        let pending: PendingTicket = Arc::new(Mutex::new(None));
+        let pending_clone = pending.clone();
+        let callback = client.register_callback(move |response: TicketForWebApiResponse| {
+            debug!(?response, "ticket for web API response");
+
+            match response.result {
+                Ok(()) => {
+                    if let Some(tx) = pending_clone.lock().unwrap().take() {
+                        let _ = tx.send(response.ticket.clone());
+                    }
+                }
+                Err(e) => {
+                    error!(error = ?e, "failed to generate ticket");
+                    // Dropping the sender ends the wait so the request is retried.
+                    pending_clone.lock().unwrap().take();
+                }
+            }
+        });
+
+        info!("Steam client initialized");
+
+        let user = client.user();
+        if !user.logged_on() {
+            error!("user is not logged into Steam");
+            return Err("user is not logged into Steam".into());
+        }
+
+        info!(steam_id = user.steam_id().raw(), "Steam user logged in");
+
+        let dispatcher_client = client.clone();
+        let dispatcher_task = tokio::spawn(async move {
+            loop {
+                dispatcher_client.run_callbacks();
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .abort_handle();
+
+        Ok(Self {
+            client,
+            identity: app_identity.to_string(),
+            pending,
+            _callback: callback,
+            dispatcher_task,
+        })
+    }
+
+    /// The web-API identity this instance requests tickets for.
+    pub fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    /// The local user's Steam ID.
+    pub fn steam_id(&self) -> u64 {
+        self.client.user().steam_id().raw()
+    }
+
+    /// Requests a ticket using [`DEFAULT_TIMEOUT`] and [`DEFAULT_RETRIES`].
+    pub async fn request_ticket(&self) -> Result<Vec<u8>> {
+        self.request_ticket_with(DEFAULT_TIMEOUT, DEFAULT_RETRIES).await
+    }
+
+    /// Requests a ticket, retrying up to `retries` times if a request times out
+    /// or Steam reports a failed ticket result.
+    #[instrument(skip(self), fields(identity = %self.identity, ticket_bytes = tracing::field::Empty))]
+    pub async fn request_ticket_with(&self, timeout: Duration, retries: u32) -> Result<Vec<u8>> {
+        let user = self.client.user();
+        let attempts = retries.saturating_add(1);
+
+        for attempt in 1..=attempts {
+            let (tx, rx) = oneshot::channel();
+            *self.pending.lock().unwrap() = Some(tx);
+
+            let handle = user.authentication_session_ticket_for_webapi(&self.identity);
+            info!(?handle, attempt, retries, "requested auth ticket");
+
+            match tokio::time::timeout(timeout, rx).await {
+                Ok(Ok(ticket)) => {
+                    tracing::Span::current().record("ticket_bytes", ticket.len());
+                    info!(bytes = ticket.len(), "ticket acquired");
+                    return Ok(ticket);
+                }
+                Ok(Err(_)) => warn!(attempt, retries, "ticket request failed"),
+                Err(_) => warn!(attempt, retries, "timed out waiting for ticket response"),
+            }
+
+            *self.pending.lock().unwrap() = None;
+        }
+
+        error!(retries, "failed to acquire ticket");
+        Err(format!("failed to acquire ticket after {} attempt(s)", attempts).into())
+    }
+}
+
+impl Drop for SteamAuth {
+    /// Stops the background `run_callbacks` pump so reconnecting hosts don't
+    /// accumulate one never-ending task per dropped `SteamAuth`.
+    fn drop(&mut self) {
+        self.dispatcher_task.abort();
+    }
+}
+
+/// Hex-encodes a ticket the way it's written to files and POSTed to endpoints.
+pub fn hex_encode(ticket: &[u8]) -> String {
+    ticket.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Tries each URL in `urls` in order, moving on only when the previous one
+/// errors or returns a non-200 status. Returns the URL that succeeded, or an
+/// aggregate error if every endpoint failed.
+pub async fn post_ticket_to_urls<'a>(
+    urls: &'a [String],
+    email: &str,
+    ticket: &[u8],
+    timeout: Duration,
+) -> Result<&'a str> {
+    let client = reqwest::Client::builder().timeout(timeout).build()?;
+    let mut failures = Vec::new();
+
+    for url in urls {
+        match post_ticket_with_client(&client, url, email, ticket).await {
+            Ok(()) => return Ok(url),
+            Err(e) => {
+                warn!(url, error = %e, "endpoint failed");
+                failures.push(format!("{}: {}", url, e));
+            }
+        }
+    }
+
+    error!(endpoints = urls.len(), "all endpoints failed");
+    Err(format!("all {} endpoint(s) failed: {}", urls.len(), failures.join("; ")).into())
+}
+
+/// POSTs a hex-encoded ticket and email to `url` as query parameters.
+pub async fn post_ticket_to_url(url: &str, email: &str, ticket: &[u8], timeout: Duration) -> Result<()> {
+    let client = reqwest::Client::builder().timeout(timeout).build()?;
+    post_ticket_with_client(&client, url, email, ticket).await
+}
+
+async fn post_ticket_with_client(client: &reqwest::Client, url: &str, email: &str, ticket: &[u8]) -> Result<()> {
+    let hex_ticket = hex_encode(ticket);
+
+    let response = client
+        .post(url)
+        .query(&[("email", email), ("authTicket", &hex_ticket)])
+        .send()
+        .await?;
+
+    if response.status() == 200 {
+        Ok(())
+    } else {
+        Err(format!("Server returned status: {}", response.status()).into())
+    }
+}
+
+/// Writes a ticket to `filename` as a hex string.
+pub fn write_ticket_to_file(ticket: &[u8], filename: &str) -> Result<()> {
+    use std::fs::File;
+    use std::io::Write;
+
+    let mut file = File::create(filename)?;
+    writeln!(file, "{}", hex_encode(ticket))?;
+    Ok(())
+}
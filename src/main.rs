@@ -1,10 +1,13 @@
 use clap::Parser;
-use reqwest;
-use std::fs::File;
-use std::io::{stdin, Write};
-use std::sync::{Arc, Mutex};
-use steamworks::{Client, TicketForWebApiResponse};
+use std::io::stdin;
+use std::time::Duration;
+use steam_auth::verify::VerifyOutcome;
+use steam_auth::SteamAuth;
+use tracing::{error, info, Level};
+use tracing_subscriber::EnvFilter;
 
+/// Default web-API identity when none is supplied on the command line.
+const DEFAULT_IDENTITY: &str = "BitCraftApiServer";
 
 #[derive(Parser)]
 #[command(name = "steam-auth")]
@@ -12,19 +15,22 @@ use steamworks::{Client, TicketForWebApiResponse};
 #[command(
     long_about = "Generates Steam authentication tickets for web API usage. Can either POST the ticket to a URL with email credentials or save it to a local file."
 )]
+#[command(group(clap::ArgGroup::new("post_mode").args(["url", "email"]).multiple(true)))]
 struct Args {
-    /// URL endpoint to POST the authentication ticket to
+    /// URL endpoint(s) to POST the authentication ticket to
     ///
-    /// When provided, must be used together with `--email`. The ticket will be sent
-    /// as a POST request to this URL with `email` and `authTicket` query parameters.
+    /// Repeatable (or comma-separated) to configure a fallback chain: each
+    /// endpoint is tried in order and the ticket is posted to the next one
+    /// only if the previous one errors or returns a non-200 status. Must be
+    /// used together with `--email`.
     #[arg(
         long,
         short = 'u',
-        group = "post_mode",
         requires = "email",
-        help = "URL to POST authentication ticket to"
+        value_delimiter = ',',
+        help = "URL(s) to POST authentication ticket to, tried in order"
     )]
-    url: Option<String>,
+    url: Vec<String>,
 
     /// Email address to send with the authentication ticket
     ///
@@ -33,7 +39,6 @@ struct Args {
     #[arg(
         long,
         short = 'e',
-        group = "post_mode",
         requires = "url",
         help = "Email to send with auth ticket"
     )]
@@ -64,142 +69,247 @@ struct Args {
         help = "Exit immediately after writing ticket file"
     )]
     exit: bool,
-}
 
-#[tokio::main]
-async fn main() {
-    let args = Args::parse();
+    /// Web-API identity to request tickets for
+    #[arg(long, default_value = DEFAULT_IDENTITY, help = "Web-API identity to request tickets for")]
+    identity: String,
+
+    /// Reuse a still-valid cached ticket instead of contacting Steam
+    ///
+    /// Cached tickets are stored under `~/.cache/steam-auth/tickets` with 0600
+    /// permissions, keyed by the web-API identity. Enabled by default.
+    #[arg(long, default_value_t = true, overrides_with = "no_cache", help = "Use the on-disk ticket cache")]
+    cache: bool,
 
+    /// Disable the on-disk ticket cache and always request a fresh ticket
+    #[arg(long, default_value_t = false, overrides_with = "cache", help = "Disable the on-disk ticket cache")]
+    no_cache: bool,
 
-    // Initialize Steam client
-    let client = match Client::init() {
-        Ok(client) => client,
-        Err(e) => {
-            eprintln!("Failed to initialize Steam client: {:?}", e);
-            return;
+    /// How long a cached ticket remains valid for reuse, in seconds
+    #[arg(long, default_value_t = 1800, help = "Cached ticket TTL in seconds")]
+    ttl: u64,
+
+    /// Run as a long-lived daemon that keeps renewing the ticket
+    ///
+    /// Instead of exiting (or idling until Enter) after the first ticket, the
+    /// Steam client and callback stay registered and a fresh ticket is
+    /// requested every `--refresh-interval` seconds, so a sidecar process can
+    /// keep feeding a web backend a ticket that never goes stale.
+    #[arg(long, conflicts_with = "exit", help = "Run as a daemon, renewing the ticket periodically")]
+    daemon: bool,
+
+    /// Seconds between ticket renewals in `--daemon` mode
+    #[arg(long, default_value_t = 1800, help = "Ticket renewal interval in seconds (daemon mode)")]
+    refresh_interval: u64,
+
+    /// How long to wait for a ticket response before giving up (or retrying)
+    ///
+    /// Also applied to the `reqwest` client used for `--url` POSTs.
+    #[arg(long, default_value_t = 120, help = "Ticket/POST timeout in seconds")]
+    timeout: u64,
+
+    /// Number of times to re-request a ticket if a response times out or errors
+    #[arg(long, default_value_t = 3, help = "Ticket request retries")]
+    retries: u32,
+
+    /// Verify the acquired ticket against Steam's Web API before delivering it
+    ///
+    /// Calls `ISteamUserAuth/AuthenticateUserTicket` with the given publisher
+    /// Web API key and app ID to confirm the ticket is accepted and resolve
+    /// the SteamID it belongs to, rather than blindly trusting an opaque hex
+    /// blob. Exits non-zero if verification fails; in `--daemon` mode a failed
+    /// verification is logged, that ticket is not delivered, and the daemon
+    /// keeps running.
+    #[arg(long, requires_all = ["webapi_key", "app_id"], help = "Verify the ticket via ISteamUserAuth/AuthenticateUserTicket")]
+    verify: bool,
+
+    /// Publisher Web API key used for --verify
+    #[arg(long, help = "Steam Web API key (required for --verify)")]
+    webapi_key: Option<String>,
+
+    /// Steam app ID the ticket was issued for, used for --verify
+    #[arg(long, help = "Steam app ID (required for --verify)")]
+    app_id: Option<u32>,
+
+    /// Increase log verbosity (-v = debug, -vv = trace)
+    #[arg(long, short = 'v', action = clap::ArgAction::Count, conflicts_with = "quiet", help = "Increase log verbosity")]
+    verbose: u8,
+
+    /// Suppress all but error-level logs
+    #[arg(long, conflicts_with = "verbose", help = "Suppress all but error-level logs")]
+    quiet: bool,
+}
+
+/// Picks the subscriber's level filter from `-v`/`--quiet`, honoring
+/// `RUST_LOG` if the user has set it explicitly.
+fn init_tracing(verbose: u8, quiet: bool) {
+    let default_level = if quiet {
+        Level::ERROR
+    } else {
+        match verbose {
+            0 => Level::INFO,
+            1 => Level::DEBUG,
+            _ => Level::TRACE,
         }
     };
 
-    println!("Steam client initialized successfully!");
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level.to_string()));
+
+    tracing_subscriber::fmt().with_env_filter(filter).init();
+}
 
-    // Shared state to store the ticket when callback fires
-    let ticket_data = Arc::new(Mutex::new(None::<Vec<u8>>));
-    let ticket_data_clone = ticket_data.clone();
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    init_tracing(args.verbose, args.quiet);
 
-    // Register callback for ticket response
-    let _cb = client.register_callback(move |response: TicketForWebApiResponse| {
-        println!("Got ticket response: {:?}", response);
+    let use_cache = args.cache && !args.no_cache;
+    let ttl = Duration::from_secs(args.ttl);
 
-        match response.result {
-            Ok(()) => {
-                println!("Ticket generated successfully, {} bytes", response.ticket.len());
-                *ticket_data_clone.lock().unwrap() = Some(response.ticket.clone());
-            }
-            Err(e) => {
-                eprintln!("Failed to generate ticket: {:?}", e);
+    // A still-valid cached ticket lets us skip Steam entirely. Daemon mode
+    // always renews on its own schedule, so it never reads the cache (it
+    // still writes each verified ticket to it).
+    if use_cache && !args.daemon {
+        if let Some(ticket) = steam_auth::cache::read_cached_ticket(&args.identity, ttl) {
+            info!(bytes = ticket.len(), "using cached ticket");
+
+            if !verify_ticket(&args, &ticket, Duration::from_secs(args.timeout)).await {
+                std::process::exit(1);
             }
-        }
-    });
 
-    // Get user and check login status
-    let user = client.user();
-    if !user.logged_on() {
-        eprintln!("User is not logged into Steam");
-        return;
+            deliver_ticket(&args, &ticket, true).await;
+            return;
+        }
     }
 
-    println!("Steam ID: {}", user.steam_id().raw());
+    let auth = match SteamAuth::connect(&args.identity) {
+        Ok(auth) => auth,
+        Err(e) => {
+            error!(error = %e, "failed to connect to Steam");
+            return;
+        }
+    };
 
-    // Request auth ticket for web API
-    let auth_ticket_handle = user.authentication_session_ticket_for_webapi("BitCraftApiServer");
-    println!("Auth ticket handle: {:?}", auth_ticket_handle);
-    println!("Waiting for ticket response...");
+    info!(steam_id = auth.steam_id(), "ready to request tickets");
 
-    // Wait for callback to receive actual ticket data
-    let mut ticket_received = false;
-    let mut attempts = 0;
-    while !ticket_received && attempts < 100 {
-        client.run_callbacks();
+    let timeout = Duration::from_secs(args.timeout);
 
-        if let Some(ticket) = ticket_data.lock().unwrap().as_ref() {
-            println!("Received ticket with {} bytes", ticket.len());
+    if args.daemon {
+        let refresh_interval = Duration::from_secs(args.refresh_interval);
+        loop {
+            match auth.request_ticket_with(timeout, args.retries).await {
+                Ok(ticket) => {
+                    // Daemon mode logs the verification result but keeps running either way.
+                    let verified = verify_ticket(&args, &ticket, timeout).await;
 
-            // Either POST to URL or write to file
-            if let (Some(url), Some(email)) = (&args.url, &args.email) {
-                println!("Attempting to post ticket to URL: {} with email: {}", url, email);
-                match post_ticket_to_url(url, email, ticket).await {
-                    Ok(_) => {
-                        println!("Successfully authenticated!");
-                        std::process::exit(0);
-                    } // Succeed silently on 200 OK
-                    Err(e) => {
-                        eprintln!("Error posting ticket: {}", e);
-                        panic!("Failed to post auth ticket");
+                    if use_cache && verified {
+                        if let Err(e) = steam_auth::cache::write_cached_ticket(&args.identity, &ticket) {
+                            error!(error = ?e, "failed to write ticket cache");
+                        }
+                    }
+
+                    if verified {
+                        deliver_ticket(&args, &ticket, false).await;
                     }
                 }
-            } else {
-                match write_ticket_to_file(ticket, &args.output_file) {
-                    Ok(_) => println!("Ticket written to {}", args.output_file),
-                    Err(e) => eprintln!("Failed to write ticket to file: {:?}", e),
-                }
+                Err(e) => error!(error = %e, "failed to acquire ticket"),
             }
-            ticket_received = true;
-        } else {
-            attempts += 1;
-            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+            info!(seconds = refresh_interval.as_secs(), "next renewal scheduled");
+            tokio::time::sleep(refresh_interval).await;
         }
     }
 
-    if !ticket_received {
-        eprintln!("Timeout waiting for ticket response");
+    // Request + wait for a ticket, retrying on timeout
+    match auth.request_ticket_with(timeout, args.retries).await {
+        Ok(ticket) => {
+            if !verify_ticket(&args, &ticket, timeout).await {
+                std::process::exit(1);
+            }
+
+            if use_cache {
+                if let Err(e) = steam_auth::cache::write_cached_ticket(&args.identity, &ticket) {
+                    error!(error = ?e, "failed to write ticket cache");
+                }
+            }
+
+            deliver_ticket(&args, &ticket, true).await;
+        }
+        Err(e) => error!(error = %e, "failed to acquire ticket"),
     }
 
     // Keep Steam client alive until Enter is pressed
-    println!("Session held open. Press Enter to exit...");
-
-    std::thread::spawn(|| {
-        let mut input = String::new();
-        stdin().read_line(&mut input).unwrap();
-        std::process::exit(0);
-    });
-
-    // Keep running callbacks forever until user presses Enter
-    // this is to allow user to post the ticket manually
-    loop {
-        client.run_callbacks();
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-    }
-}
+    info!("session held open, press Enter to exit");
 
+    let mut input = String::new();
+    stdin().read_line(&mut input).unwrap();
+}
 
-async fn post_ticket_to_url(url: &str, email: &str, ticket: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
-    let hex_ticket = ticket.iter()
-        .map(|byte| format!("{:02x}", byte))
-        .collect::<String>();
+/// Verifies the ticket via `--webapi-key`/`--app-id` if `--verify` was passed.
+///
+/// Returns `true` when verification was not requested or succeeded, `false`
+/// when the ticket was rejected as expired/invalid or the request itself failed.
+async fn verify_ticket(args: &Args, ticket: &[u8], timeout: Duration) -> bool {
+    if !args.verify {
+        return true;
+    }
 
-    let client = reqwest::Client::new();
-    let response = client
-        .post(url)
-        .query(&[("email", email), ("authTicket", &hex_ticket)])
-        .send()
-        .await?;
+    let (Some(webapi_key), Some(app_id)) = (&args.webapi_key, args.app_id) else {
+        return true;
+    };
 
-    if response.status() == 200 {
-        Ok(())
-    } else {
-        Err(format!("Server returned status: {}", response.status()).into())
+    match steam_auth::verify::verify_ticket(webapi_key, app_id, ticket, timeout).await {
+        Ok(VerifyOutcome::Ok { steam_id }) => {
+            info!(steam_id, "ticket verified by Steam");
+            true
+        }
+        Ok(VerifyOutcome::Expired) => {
+            error!("ticket verification failed: ticket expired");
+            false
+        }
+        Ok(VerifyOutcome::Invalid) => {
+            error!("ticket verification failed: ticket invalid");
+            false
+        }
+        Err(e) => {
+            error!(error = %e, "ticket verification request failed");
+            false
+        }
     }
 }
 
-fn write_ticket_to_file(ticket: &[u8], filename: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut file = File::create(filename)?;
-
-    // Write ticket as hex string
-    let hex_ticket = ticket.iter()
-        .map(|byte| format!("{:02x}", byte))
-        .collect::<String>();
-
-    writeln!(file, "{}", hex_ticket)?;
+/// Either POSTs the ticket to `args.url`/`args.email` or writes it to `args.output_file`.
+///
+/// `exit_on_success` is `false` in `--daemon` mode, where the process must
+/// keep running to renew the ticket instead of exiting after the first POST.
+async fn deliver_ticket(args: &Args, ticket: &[u8], exit_on_success: bool) {
+    if let Some(email) = &args.email {
+        if !args.url.is_empty() {
+            match steam_auth::post_ticket_to_urls(&args.url, email, ticket, Duration::from_secs(args.timeout)).await {
+                Ok(url) => {
+                    info!(url, "successfully authenticated");
+                    if exit_on_success {
+                        std::process::exit(0);
+                    }
+                }
+                Err(e) => {
+                    error!(error = %e, "error posting ticket");
+                    if exit_on_success {
+                        panic!("Failed to post auth ticket");
+                    }
+                }
+            }
+            return;
+        }
+    }
 
-    Ok(())
+    match steam_auth::write_ticket_to_file(ticket, &args.output_file) {
+        Ok(_) => {
+            info!(file = %args.output_file, "ticket written");
+            if exit_on_success && args.exit {
+                std::process::exit(0);
+            }
+        }
+        Err(e) => error!(error = ?e, "failed to write ticket to file"),
+    }
 }
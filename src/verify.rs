@@ -0,0 +1,113 @@
+//! Server-side verification of an issued ticket against Steam's Web API.
+
+use crate::{hex_encode, Result};
+use std::time::Duration;
+use tracing::{error, info, instrument, warn};
+
+const AUTHENTICATE_USER_TICKET_URL: &str = "https://api.steampowered.com/ISteamUserAuth/AuthenticateUserTicket/v1/";
+
+/// Outcome of verifying a ticket against Steam's Web API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyOutcome {
+    /// The ticket is valid and resolves to this SteamID.
+    Ok { steam_id: u64 },
+    /// The ticket has expired.
+    Expired,
+    /// The ticket was rejected as invalid.
+    Invalid,
+}
+
+/// Calls `ISteamUserAuth/AuthenticateUserTicket` to confirm `ticket` is
+/// accepted for `app_id`, returning the resolved SteamID on success.
+#[instrument(skip(webapi_key, ticket), fields(app_id))]
+pub async fn verify_ticket(webapi_key: &str, app_id: u32, ticket: &[u8], timeout: Duration) -> Result<VerifyOutcome> {
+    let hex_ticket = hex_encode(ticket);
+    let app_id = app_id.to_string();
+
+    let client = reqwest::Client::builder().timeout(timeout).build()?;
+    let response = client
+        .get(AUTHENTICATE_USER_TICKET_URL)
+        .query(&[("key", webapi_key), ("appid", &app_id), ("ticket", &hex_ticket)])
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        error!(status = %response.status(), "AuthenticateUserTicket request failed");
+        return Err(format!("AuthenticateUserTicket returned status: {}", response.status()).into());
+    }
+
+    let body: serde_json::Value = response.json().await?;
+    let outcome = parse_response(&body)?;
+
+    match outcome {
+        VerifyOutcome::Ok { steam_id } => info!(steam_id, "ticket verified"),
+        VerifyOutcome::Expired | VerifyOutcome::Invalid => warn!(?outcome, "ticket not accepted"),
+    }
+
+    Ok(outcome)
+}
+
+/// Parses an `AuthenticateUserTicket` JSON body into a [`VerifyOutcome`].
+fn parse_response(body: &serde_json::Value) -> Result<VerifyOutcome> {
+    let inner = &body["response"];
+
+    if let Some(error) = inner.get("error") {
+        let desc = error["errordesc"].as_str().unwrap_or("unknown error");
+        warn!(desc, "ticket rejected as invalid");
+        return Ok(VerifyOutcome::Invalid);
+    }
+
+    let params = &inner["params"];
+    let result = params["result"].as_str().unwrap_or_default();
+
+    if !result.eq_ignore_ascii_case("OK") {
+        return Ok(if result.eq_ignore_ascii_case("expired") {
+            VerifyOutcome::Expired
+        } else {
+            VerifyOutcome::Invalid
+        });
+    }
+
+    let steam_id: u64 = params["steamid"]
+        .as_str()
+        .and_then(|s| s.parse().ok())
+        .ok_or("AuthenticateUserTicket response missing steamid")?;
+
+    Ok(VerifyOutcome::Ok { steam_id })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_ok_response() {
+        let body = json!({"response": {"params": {"result": "OK", "steamid": "76561198000000000"}}});
+        assert_eq!(parse_response(&body).unwrap(), VerifyOutcome::Ok { steam_id: 76561198000000000 });
+    }
+
+    #[test]
+    fn parses_expired_response() {
+        let body = json!({"response": {"params": {"result": "Expired"}}});
+        assert_eq!(parse_response(&body).unwrap(), VerifyOutcome::Expired);
+    }
+
+    #[test]
+    fn parses_invalid_result() {
+        let body = json!({"response": {"params": {"result": "Invalid"}}});
+        assert_eq!(parse_response(&body).unwrap(), VerifyOutcome::Invalid);
+    }
+
+    #[test]
+    fn parses_error_shape_as_invalid() {
+        let body = json!({"response": {"error": {"errorcode": 101, "errordesc": "Ticket is not valid."}}});
+        assert_eq!(parse_response(&body).unwrap(), VerifyOutcome::Invalid);
+    }
+
+    #[test]
+    fn missing_steamid_on_ok_is_an_error() {
+        let body = json!({"response": {"params": {"result": "OK"}}});
+        assert!(parse_response(&body).is_err());
+    }
+}